@@ -0,0 +1,143 @@
+use std::fs;
+
+use crossterm::style::Color;
+use serde::Deserialize;
+
+pub const CONFIG_PATH: &str = "./floormedia.toml";
+
+/// The full set of subservers the launcher orchestrates, loaded from
+/// `floormedia.toml` at startup.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Config {
+    #[serde(rename = "subserver")]
+    pub subservers: Vec<SubserverConfig>,
+}
+
+/// One managed subserver: where it lives, how it's built and started, and
+/// how it's displayed.
+#[derive(Debug, Clone, Deserialize)]
+pub struct SubserverConfig {
+    pub name: String,
+    /// Overrides the `<base_url><name>.git` derivation in `git_clone`
+    /// when the subserver's repo doesn't follow that convention.
+    pub repo_url: Option<String>,
+    /// Argv of each step to run in order during a build, e.g.
+    /// `[["npm", "install"], ["npm", "run", "build"]]`.
+    pub build_steps: Vec<Vec<String>>,
+    /// Argv used to start the subserver, e.g. `["npm", "start"]`.
+    pub start_command: Vec<String>,
+    /// Extra arg appended (after a literal `--`) when a port override is
+    /// given for this server, with `{port}` replaced by the chosen port,
+    /// e.g. `"-p={port}"`.
+    pub port_arg_template: Option<String>,
+    #[serde(default)]
+    pub color: SubserverColor,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum SubserverColor {
+    #[default]
+    Blue,
+    DarkMagenta,
+    Green,
+    DarkGreen,
+    Yellow,
+    DarkYellow,
+    Cyan,
+    DarkCyan,
+    Red,
+    DarkRed,
+}
+impl SubserverColor {
+    pub fn as_crossterm(self) -> Color {
+        match self {
+            Self::Blue => Color::Blue,
+            Self::DarkMagenta => Color::DarkMagenta,
+            Self::Green => Color::Green,
+            Self::DarkGreen => Color::DarkGreen,
+            Self::Yellow => Color::Yellow,
+            Self::DarkYellow => Color::DarkYellow,
+            Self::Cyan => Color::Cyan,
+            Self::DarkCyan => Color::DarkCyan,
+            Self::Red => Color::Red,
+            Self::DarkRed => Color::DarkRed,
+        }
+    }
+}
+
+pub fn load() -> Config {
+    let text = fs::read_to_string(CONFIG_PATH)
+        .unwrap_or_else(|err| panic!("could not read {}: {}", CONFIG_PATH, err));
+    toml::from_str(&text).unwrap_or_else(|err| panic!("invalid {}: {}", CONFIG_PATH, err))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_one_subserver_with_defaults() {
+        let config: Config = toml::from_str(
+            r#"
+            [[subserver]]
+            name = "floormedia_backend"
+            build_steps = [["npm", "install"]]
+            start_command = ["npm", "start"]
+            "#,
+        )
+        .unwrap();
+
+        let sub = &config.subservers[0];
+        assert_eq!(sub.name, "floormedia_backend");
+        assert_eq!(sub.repo_url, None);
+        assert_eq!(sub.port_arg_template, None);
+        assert!(matches!(sub.color, SubserverColor::Blue));
+    }
+
+    #[test]
+    fn parses_multiple_subservers_with_overrides() {
+        let config: Config = toml::from_str(
+            r#"
+            [[subserver]]
+            name = "a"
+            build_steps = [["npm", "install"], ["npm", "run", "build"]]
+            start_command = ["npm", "start"]
+            repo_url = "git@example.com:org/a.git"
+            port_arg_template = "-p={port}"
+            color = "dark_magenta"
+
+            [[subserver]]
+            name = "b"
+            build_steps = []
+            start_command = ["npm", "start"]
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(config.subservers.len(), 2);
+        assert_eq!(
+            config.subservers[0].repo_url.as_deref(),
+            Some("git@example.com:org/a.git")
+        );
+        assert!(matches!(
+            config.subservers[0].color,
+            SubserverColor::DarkMagenta
+        ));
+        assert!(matches!(config.subservers[1].color, SubserverColor::Blue));
+    }
+
+    #[test]
+    fn rejects_invalid_color() {
+        let result: Result<Config, _> = toml::from_str(
+            r#"
+            [[subserver]]
+            name = "a"
+            build_steps = []
+            start_command = ["npm", "start"]
+            color = "not_a_color"
+            "#,
+        );
+        assert!(result.is_err());
+    }
+}
@@ -1,44 +1,100 @@
+mod config;
+
 use std::{
+    collections::{HashMap, HashSet},
     env, fs,
-    io::{stdout, Read, Write},
+    io::{self, stdout, BufRead, IsTerminal, Read, Write},
+    os::fd::{AsRawFd, FromRawFd, OwnedFd, RawFd},
     path::{Path, PathBuf},
     process::{Child, Command, Stdio},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        mpsc::{self, Sender},
+        Mutex,
+    },
     thread::{self, sleep},
-    time::Duration,
+    time::{Duration, Instant},
 };
 
 use crossterm::{
     execute, queue,
-    style::{Print, PrintStyledContent, Stylize},
+    style::{Print, PrintStyledContent, ResetColor, Stylize},
     terminal,
 };
 
-const SUBSERVER_NAMES: [&str; 2] = ["floormedia_frontend", "floormedia_backend"];
-const SUBSERVER_BACKEND_I: usize = 1;
+use config::{Config, SubserverConfig};
+
 const SUBSERVER_DIR: &str = "./sub/";
+const SHUTDOWN_GRACE_PERIOD: Duration = Duration::from_secs(5);
+
+/// Number of SIGINT/SIGTERM the parent has received. The first requests a
+/// graceful shutdown; a second, received while that is in progress, escalates
+/// to an immediate kill.
+static SHUTDOWN_REQUESTS: AtomicUsize = AtomicUsize::new(0);
+
+fn install_signal_handler() {
+    let mut signals = signal_hook::iterator::Signals::new([
+        signal_hook::consts::SIGINT,
+        signal_hook::consts::SIGTERM,
+    ])
+    .unwrap();
+    thread::spawn(move || {
+        for _ in signals.forever() {
+            SHUTDOWN_REQUESTS.fetch_add(1, Ordering::SeqCst);
+        }
+    });
+}
+
+/// Master fds of every PTY currently in use, kept in sync with the parent
+/// terminal's size on SIGWINCH.
+static PTY_MASTERS: std::sync::Mutex<Vec<RawFd>> = std::sync::Mutex::new(Vec::new());
+
+fn install_sigwinch_handler() {
+    let mut signals = signal_hook::iterator::Signals::new([signal_hook::consts::SIGWINCH]).unwrap();
+    thread::spawn(move || {
+        for _ in signals.forever() {
+            let (cols, rows) = terminal::size().unwrap_or((80, 24));
+            for fd in PTY_MASTERS.lock().unwrap().iter() {
+                pty_set_winsize(*fd, cols, rows);
+            }
+        }
+    });
+}
+
+fn restore_terminal() {
+    let _ = terminal::disable_raw_mode();
+    execute!(stdout(), ResetColor).ok();
+}
 
 fn main() {
     let args: ParsedArgs = env::args().into();
+    let config = config::load();
 
-    if subservers_present() {
-        subservers_sync();
+    if subservers_present(&config) {
+        subservers_sync(&config, args.jobs);
     } else {
-        subservers_initialize();
+        subservers_initialize(&config, args.jobs);
     }
-    subservers_run(args);
+    subservers_run(&config, args);
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone)]
 struct ParsedArgs {
     distinguish_child_stdouts: bool,
-    server_alternate_port: Option<u16>,
+    port_overrides: Vec<(String, u16)>,
+    use_pty: bool,
+    watch: bool,
+    jobs: usize,
 }
 impl<T: Iterator<Item = String>> From<T> for ParsedArgs {
     fn from(value: T) -> Self {
         let mut value = value.skip(1);
         let mut out = Self {
             distinguish_child_stdouts: true,
-            server_alternate_port: None,
+            port_overrides: Vec::new(),
+            use_pty: stdout().is_terminal(),
+            watch: false,
+            jobs: thread::available_parallelism().map_or(1, |n| n.get()),
         };
         loop {
             let Some(arg) = value.next() else {
@@ -48,18 +104,43 @@ impl<T: Iterator<Item = String>> From<T> for ParsedArgs {
                 "inherit_stdouts" | "-m" => {
                     out.distinguish_child_stdouts = false;
                 }
-                "backend_port" | "-bp" => {
-                    let Some(port) = value.next().and_then(|v| v.parse::<u16>().ok()) else {
+                "port" | "-p" => {
+                    let Some((name, port)) = value.next().and_then(|spec| {
+                        let (name, port) = spec.split_once('=')?;
+                        Some((name.to_string(), port.parse::<u16>().ok()?))
+                    }) else {
                         execute!(
                             stdout(),
                             PrintStyledContent(" ".on_red()),
                             Print("  "),
-                            PrintStyledContent(format!("invalid server port, ignoring.").red()),
+                            PrintStyledContent(
+                                format!("invalid port override, expected name=port, ignoring.")
+                                    .red()
+                            ),
                         )
                         .unwrap();
                         continue;
                     };
-                    out.server_alternate_port = Some(port);
+                    out.port_overrides.push((name, port));
+                }
+                "no_pty" | "-np" => {
+                    out.use_pty = false;
+                }
+                "watch" | "-w" | "--watch" => {
+                    out.watch = true;
+                }
+                "jobs" | "-j" => {
+                    let Some(jobs) = value.next().and_then(|v| v.parse::<usize>().ok()) else {
+                        execute!(
+                            stdout(),
+                            PrintStyledContent(" ".on_red()),
+                            Print("  "),
+                            PrintStyledContent(format!("invalid job count, ignoring.").red()),
+                        )
+                        .unwrap();
+                        continue;
+                    };
+                    out.jobs = jobs;
                 }
                 _ => {
                     execute!(
@@ -76,6 +157,13 @@ impl<T: Iterator<Item = String>> From<T> for ParsedArgs {
         out
     }
 }
+impl ParsedArgs {
+    fn port_override_for(&self, name: &str) -> Option<u16> {
+        self.port_overrides
+            .iter()
+            .find_map(|(n, port)| if n == name { Some(*port) } else { None })
+    }
+}
 
 fn get_base_url() -> String {
     String::from_utf8(
@@ -98,9 +186,9 @@ fn get_subserver_cwd(name: &str) -> PathBuf {
         .unwrap()
 }
 
-fn subservers_present() -> bool {
+fn subservers_present(config: &Config) -> bool {
     fs::read_dir(SUBSERVER_DIR).is_ok_and(|entries| {
-        let mut subserver_is_ok = SUBSERVER_NAMES.map(|_| false);
+        let mut subserver_is_ok = vec![false; config.subservers.len()];
         for entry in entries {
             match entry {
                 Ok(entry) => {
@@ -108,11 +196,7 @@ fn subservers_present() -> bool {
                     let Some(name) = name.to_str() else {
                         continue;
                     };
-                    let Some(i) = SUBSERVER_NAMES
-                        .into_iter()
-                        .enumerate()
-                        .find_map(|(i, name_test)| if name == name_test { Some(i) } else { None })
-                    else {
+                    let Some(i) = config.subservers.iter().position(|sub| sub.name == name) else {
                         continue;
                     };
                     subserver_is_ok[i] = true;
@@ -125,110 +209,645 @@ fn subservers_present() -> bool {
         subserver_is_ok.into_iter().all(|v| v)
     })
 }
-fn subservers_initialize() {
+fn subservers_initialize(config: &Config, jobs: usize) {
     Style::Header.println(format!("initializing servers"));
     if fs::read_dir(SUBSERVER_DIR).is_err() {
         fs::create_dir(SUBSERVER_DIR).unwrap()
     }
-    git_clone();
-    for name in SUBSERVER_NAMES {
-        node_build(name);
-    }
+    git_clone(config);
+    build_all_parallel(config, config.subservers.iter().collect(), jobs);
 }
-fn subservers_sync() {
+fn subservers_sync(config: &Config, jobs: usize) {
     Style::Header.println(format!("updating servers"));
-    for updated in git_pull() {
-        node_build(updated);
+    build_all_parallel(config, git_pull(config), jobs);
+}
+/// A single supervised child: its process handles, plus the restart
+/// backoff state tracked when it exits on its own.
+struct ManagedChild {
+    child: Child,
+    _pty: Option<ChildPty>,
+    input: ChildInput,
+    restart_attempts: u32,
+    next_restart_at: Option<Instant>,
+}
+impl ManagedChild {
+    fn spawned(config: &Config, sub: &SubserverConfig, args: &ParsedArgs) -> Self {
+        let (child, pty, input) = node_run(config, sub, args);
+        Self {
+            child,
+            _pty: pty,
+            input,
+            restart_attempts: 0,
+            next_restart_at: None,
+        }
     }
 }
-fn subservers_run(args: ParsedArgs) {
+
+const RESTART_BACKOFF_CAP: Duration = Duration::from_secs(4);
+
+/// Events the watch-mode poller reports back to the supervisor loop.
+enum WatchEvent {
+    Updated(String),
+}
+
+/// While `--watch` is enabled, periodically pulls every subserver's repo and
+/// reports which ones changed so the supervisor loop can rebuild and restart
+/// just that subserver.
+fn spawn_watch_thread(config: Config, tx: Sender<WatchEvent>) {
+    const WATCH_POLL_INTERVAL: Duration = Duration::from_secs(30);
+    thread::spawn(move || loop {
+        sleep(WATCH_POLL_INTERVAL);
+        if SHUTDOWN_REQUESTS.load(Ordering::SeqCst) > 0 {
+            break;
+        }
+        for updated in git_pull(&config) {
+            if tx.send(WatchEvent::Updated(updated.name.clone())).is_err() {
+                return;
+            }
+        }
+    });
+}
+
+/// Outcome of a background rebuild/restart kicked off by a watch update or a
+/// console `restart`/`rebuild` command, reported back to the supervisor loop
+/// so it can update `children` without itself blocking on the build or on
+/// `shutdown_one`'s grace-period wait.
+enum RespawnEvent {
+    BuildSucceeded(String),
+    BuildFailed(String, String),
+    Respawned(String, ManagedChild),
+}
+
+/// Rebuilds `sub` on its own thread and reports whether it succeeded. The
+/// supervisor loop only tears down the existing child once it hears back,
+/// so a slow build never blocks Ctrl+C, crash-backoff timers, or console
+/// input in the meantime.
+fn spawn_build(config: Config, sub: SubserverConfig, tx: Sender<RespawnEvent>) {
+    thread::spawn(move || {
+        let event = match node_build(&config, &sub) {
+            Ok(()) => RespawnEvent::BuildSucceeded(sub.name.clone()),
+            Err(err) => RespawnEvent::BuildFailed(sub.name.clone(), err),
+        };
+        let _ = tx.send(event);
+    });
+}
+
+/// Shuts down `old` (if any) and spawns a fresh child for `sub`, off the
+/// supervisor thread so `shutdown_one`'s up-to-`SHUTDOWN_GRACE_PERIOD` wait
+/// doesn't block it either.
+fn spawn_respawn(
+    config: Config,
+    sub: SubserverConfig,
+    args: ParsedArgs,
+    old: Option<ManagedChild>,
+    tx: Sender<RespawnEvent>,
+) {
+    thread::spawn(move || {
+        if let Some(old) = old {
+            shutdown_one(old);
+        }
+        let managed = ManagedChild::spawned(&config, &sub, &args);
+        let _ = tx.send(RespawnEvent::Respawned(sub.name.clone(), managed));
+    });
+}
+
+fn subservers_run(config: &Config, args: ParsedArgs) {
     Style::Header.println(format!("launching servers"));
     Style::SubHeader.println(format!("press `ctrl+C` to exit"));
 
-    let child_processes = SUBSERVER_NAMES.map(|name| node_run(name, args));
+    install_signal_handler();
+    install_sigwinch_handler();
 
-    for mut child in child_processes {
-        child.wait().unwrap();
+    let (watch_tx, watch_rx) = mpsc::channel();
+    if args.watch {
+        spawn_watch_thread(config.clone(), watch_tx);
+    } else {
+        drop(watch_tx);
     }
-}
 
-fn node_build(name: &str) {
-    Style::StatusInfo.println(format!("[{}] update dependencies", name));
-    if !Command::new("npm")
-        .arg("install")
-        .current_dir(get_subserver_cwd(name))
-        .status()
-        .unwrap()
-        .success()
-    {
-        panic!();
-    }
-    Style::StatusInfo.println(format!("[{}] build", name));
-    if !Command::new("npm")
-        .args(["run", "build"])
-        .current_dir(get_subserver_cwd(name))
-        .status()
-        .unwrap()
-        .success()
-    {
-        panic!();
+    let (console_tx, console_rx) = mpsc::channel();
+    if io::stdin().is_terminal() {
+        spawn_console_thread(console_tx);
+        Style::SubHeader.println(format!(
+            "console: `status`, `restart <name>`, `rebuild <name>`, `@<name>` to focus, `@<name> <text>` to send once"
+        ));
+    } else {
+        drop(console_tx);
     }
-}
-fn node_run(name: &'static str, args: ParsedArgs) -> Child {
-    let mut child = Command::new("npm")
-        .arg("start")
-        .args(if let Some(port) = args.server_alternate_port {
-            if name == SUBSERVER_NAMES[SUBSERVER_BACKEND_I] {
-                vec!["--".to_string(), format!("-p={}", port)]
-            } else {
-                Vec::new()
+
+    let mut children: HashMap<String, ManagedChild> = config
+        .subservers
+        .iter()
+        .map(|sub| (sub.name.clone(), ManagedChild::spawned(config, sub, &args)))
+        .collect();
+    let mut focused_server: Option<String> = None;
+
+    // Names with an in-flight background build/respawn, so a second watch
+    // update or console command for the same server is skipped instead of
+    // racing it.
+    let mut pending: HashSet<String> = HashSet::new();
+    let (respawn_tx, respawn_rx) = mpsc::channel();
+
+    loop {
+        if SHUTDOWN_REQUESTS.load(Ordering::SeqCst) > 0 {
+            shutdown_children(&mut children);
+            break;
+        }
+
+        while let Ok(WatchEvent::Updated(name)) = watch_rx.try_recv() {
+            let Some(sub) = config.subservers.iter().find(|sub| sub.name == name) else {
+                continue;
+            };
+            if !pending.insert(name.clone()) {
+                continue;
             }
-        } else {
-            Vec::new()
-        })
-        .current_dir(get_subserver_cwd(name))
-        .stdout(if args.distinguish_child_stdouts {
-            Stdio::piped()
-        } else {
-            Stdio::inherit()
-        })
-        .spawn()
-        .unwrap();
+            Style::StatusInfo.println(format!("[{}] rebuilding after upstream update", name));
+            spawn_build(config.clone(), sub.clone(), respawn_tx.clone());
+        }
 
-    if let Some(child_stdout) = child.stdout.take() {
-        thread::spawn(move || {
-            let n_spaces = SUBSERVER_NAMES.map(str::len).into_iter().max().unwrap() - name.len();
-            let header = match name {
-                name if name == SUBSERVER_NAMES[0] => [
-                    format!(" {}{} ", name, " ".repeat(n_spaces)).blue(),
-                    "  ".to_string().on_blue(),
-                ],
-                _ => [
-                    format!(" {}{} ", name, " ".repeat(n_spaces)).dark_magenta(),
-                    "  ".to_string().on_dark_magenta(),
-                ],
+        while let Ok(line) = console_rx.try_recv() {
+            handle_console_line(
+                config,
+                &args,
+                &mut children,
+                &mut focused_server,
+                &mut pending,
+                &respawn_tx,
+                &line,
+            );
+        }
+
+        while let Ok(event) = respawn_rx.try_recv() {
+            match event {
+                RespawnEvent::BuildSucceeded(name) => {
+                    let Some(sub) = config.subservers.iter().find(|sub| sub.name == name) else {
+                        pending.remove(&name);
+                        continue;
+                    };
+                    let old = children.remove(&name);
+                    spawn_respawn(
+                        config.clone(),
+                        sub.clone(),
+                        args.clone(),
+                        old,
+                        respawn_tx.clone(),
+                    );
+                }
+                RespawnEvent::BuildFailed(name, err) => {
+                    Style::StatusInfo.println(format!(
+                        "[{}] rebuild failed, leaving it running: {}",
+                        name, err
+                    ));
+                    pending.remove(&name);
+                }
+                RespawnEvent::Respawned(name, managed) => {
+                    pending.remove(&name);
+                    children.insert(name, managed);
+                }
+            }
+        }
+
+        for name in config
+            .subservers
+            .iter()
+            .map(|sub| sub.name.clone())
+            .collect::<Vec<_>>()
+        {
+            let Some(managed) = children.get_mut(&name) else {
+                continue;
             };
+            if let Some(deadline) = managed.next_restart_at {
+                if Instant::now() >= deadline {
+                    let attempt = managed.restart_attempts;
+                    Style::StatusInfo.println(format!(
+                        "[{}] restart attempt {}",
+                        name,
+                        attempt + 1
+                    ));
+                    let sub = config
+                        .subservers
+                        .iter()
+                        .find(|sub| sub.name == name)
+                        .unwrap();
+                    let mut respawned = ManagedChild::spawned(config, sub, &args);
+                    respawned.restart_attempts = attempt + 1;
+                    children.insert(name, respawned);
+                }
+                continue;
+            }
+            if let Some(status) = managed.child.try_wait().unwrap() {
+                if !status.success() {
+                    let backoff = Duration::from_secs(1 << managed.restart_attempts.min(2))
+                        .min(RESTART_BACKOFF_CAP);
+                    Style::StatusInfo.println(format!(
+                        "[{}] exited unexpectedly, restarting in {:?}",
+                        name, backoff
+                    ));
+                    managed.next_restart_at = Some(Instant::now() + backoff);
+                }
+            }
+        }
+
+        if !args.watch
+            && pending.is_empty()
+            && children
+                .values()
+                .all(|managed| managed.next_restart_at.is_none())
+            && children
+                .values_mut()
+                .all(|managed| managed.child.try_wait().unwrap().is_some())
+        {
+            break;
+        }
+
+        sleep(Duration::from_millis(100));
+    }
+
+    restore_terminal();
+}
+
+/// Reads lines from the parent's stdin and forwards them to the supervisor
+/// loop for interpretation, so typed input coexists with the colored output
+/// panes without the two racing on the terminal directly.
+fn spawn_console_thread(tx: Sender<String>) {
+    thread::spawn(move || {
+        for line in io::stdin().lock().lines() {
+            let Ok(line) = line else {
+                break;
+            };
+            if tx.send(line).is_err() {
+                break;
+            }
+        }
+    });
+}
+
+/// Interprets one line of console input: built-in `status`/`restart
+/// <name>`/`rebuild <name>` commands, `@<name>` to focus a server, `@<name>
+/// <text>` to send a line to a server without changing focus, or (with no
+/// prefix) a line forwarded to whichever server is currently focused.
+#[allow(clippy::too_many_arguments)]
+fn handle_console_line(
+    config: &Config,
+    args: &ParsedArgs,
+    children: &mut HashMap<String, ManagedChild>,
+    focused_server: &mut Option<String>,
+    pending: &mut HashSet<String>,
+    respawn_tx: &Sender<RespawnEvent>,
+    line: &str,
+) {
+    if line.is_empty() {
+        return;
+    }
+    if let Some(rest) = line.strip_prefix('@') {
+        let (name, text) = match rest.split_once(' ') {
+            Some((name, text)) => (name, Some(text)),
+            None => (rest, None),
+        };
+        if !children.contains_key(name) {
+            Style::StatusInfo.println(format!("console: unknown server '{}'", name));
+            return;
+        }
+        match text {
+            Some(text) => console_send(children, name, text),
+            None => {
+                *focused_server = Some(name.to_string());
+                Style::StatusInfo.println(format!("console: focused on [{}]", name));
+            }
+        }
+        return;
+    }
+    if let Some(name) = line.strip_prefix("restart ") {
+        console_restart(config, args, children, pending, respawn_tx, name.trim());
+        return;
+    }
+    if let Some(name) = line.strip_prefix("rebuild ") {
+        console_rebuild(config, children, pending, respawn_tx, name.trim());
+        return;
+    }
+    if line == "status" {
+        console_status(children);
+        return;
+    }
+    match focused_server.clone() {
+        Some(name) => console_send(children, &name, line),
+        None => Style::StatusInfo.println(format!(
+            "console: no server focused, use @<name> to focus one first"
+        )),
+    }
+}
+
+fn console_send(children: &mut HashMap<String, ManagedChild>, name: &str, line: &str) {
+    let Some(managed) = children.get_mut(name) else {
+        Style::StatusInfo.println(format!("console: unknown server '{}'", name));
+        return;
+    };
+    if let Err(err) = managed.input.send_line(line) {
+        Style::StatusInfo.println(format!("[{}] failed to write to stdin: {}", name, err));
+    }
+}
+
+/// Tears down and respawns a server on a background thread, reporting back
+/// through `respawn_tx` the same way a watch-triggered restart does, so the
+/// up-to-`SHUTDOWN_GRACE_PERIOD` shutdown wait never blocks the console.
+fn console_restart(
+    config: &Config,
+    args: &ParsedArgs,
+    children: &mut HashMap<String, ManagedChild>,
+    pending: &mut HashSet<String>,
+    respawn_tx: &Sender<RespawnEvent>,
+    name: &str,
+) {
+    let Some(sub) = config.subservers.iter().find(|sub| sub.name == name) else {
+        Style::StatusInfo.println(format!("console: unknown server '{}'", name));
+        return;
+    };
+    if !pending.insert(name.to_string()) {
+        Style::StatusInfo.println(format!("[{}] already restarting/rebuilding", name));
+        return;
+    }
+    Style::StatusInfo.println(format!("[{}] restarting (console command)", name));
+    let old = children.remove(name);
+    spawn_respawn(
+        config.clone(),
+        sub.clone(),
+        args.clone(),
+        old,
+        respawn_tx.clone(),
+    );
+}
+
+/// Kicks off a background rebuild; the supervisor loop's `RespawnEvent`
+/// handling restarts the server once the build reports success, same as a
+/// watch-triggered rebuild.
+fn console_rebuild(
+    config: &Config,
+    children: &HashMap<String, ManagedChild>,
+    pending: &mut HashSet<String>,
+    respawn_tx: &Sender<RespawnEvent>,
+    name: &str,
+) {
+    let Some(sub) = config.subservers.iter().find(|sub| sub.name == name) else {
+        Style::StatusInfo.println(format!("console: unknown server '{}'", name));
+        return;
+    };
+    if !children.contains_key(name) {
+        Style::StatusInfo.println(format!("console: unknown server '{}'", name));
+        return;
+    }
+    if !pending.insert(name.to_string()) {
+        Style::StatusInfo.println(format!("[{}] already restarting/rebuilding", name));
+        return;
+    }
+    Style::StatusInfo.println(format!("[{}] rebuilding (console command)", name));
+    spawn_build(config.clone(), sub.clone(), respawn_tx.clone());
+}
+
+fn console_status(children: &mut HashMap<String, ManagedChild>) {
+    for (name, managed) in children.iter_mut() {
+        let state = match managed.child.try_wait().unwrap() {
+            Some(status) => format!("exited ({})", status),
+            None => "running".to_string(),
+        };
+        Style::StatusInfo.println(format!("[{}] {}", name, state));
+    }
+}
+
+fn shutdown_one(mut managed: ManagedChild) {
+    unsafe {
+        libc::kill(managed.child.id() as libc::pid_t, libc::SIGTERM);
+    }
+    let deadline = Instant::now() + SHUTDOWN_GRACE_PERIOD;
+    while managed.child.try_wait().unwrap().is_none() && Instant::now() < deadline {
+        sleep(Duration::from_millis(50));
+    }
+    if managed.child.try_wait().unwrap().is_none() {
+        let _ = managed.child.kill();
+    }
+    let _ = managed.child.wait();
+}
+
+/// Forwards SIGTERM to every child, gives them `SHUTDOWN_GRACE_PERIOD` to
+/// exit on their own and flush their piped output, then SIGKILLs any
+/// stragglers. A second interrupt received during the grace period skips
+/// straight to SIGKILL.
+fn shutdown_children(children: &mut HashMap<String, ManagedChild>) {
+    Style::StatusInfo.println(format!("shutting down servers"));
+    for managed in children.values() {
+        unsafe {
+            libc::kill(managed.child.id() as libc::pid_t, libc::SIGTERM);
+        }
+    }
+
+    let deadline = Instant::now() + SHUTDOWN_GRACE_PERIOD;
+    loop {
+        let all_exited = children
+            .values_mut()
+            .all(|managed| managed.child.try_wait().unwrap().is_some());
+        if all_exited {
+            return;
+        }
+        if SHUTDOWN_REQUESTS.load(Ordering::SeqCst) > 1 || Instant::now() >= deadline {
+            break;
+        }
+        sleep(Duration::from_millis(50));
+    }
+
+    Style::StatusInfo.println(format!("force killing remaining servers"));
+    for managed in children.values_mut() {
+        if managed.child.try_wait().unwrap().is_none() {
+            let _ = managed.child.kill();
+        }
+        let _ = managed.child.wait();
+    }
+}
+
+fn node_build(config: &Config, sub: &SubserverConfig) -> Result<(), String> {
+    let header = header_for(config, sub);
+    for step in &sub.build_steps {
+        let [program, step_args @ ..] = step.as_slice() else {
+            continue;
+        };
+        Style::StatusInfo.println(format!("[{}] {}", sub.name, step.join(" ")));
+        let mut child = Command::new(program)
+            .args(step_args)
+            .current_dir(get_subserver_cwd(&sub.name))
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|err| format!("[{}] failed to run `{}`: {}", sub.name, step.join(" "), err))?;
+
+        let stdout_forwarder = child.stdout.take().map(|out| {
+            spawn_line_forwarder(sub.name.clone(), header.clone(), StreamKind::Stdout, out)
+        });
+        let stderr_forwarder = child.stderr.take().map(|err| {
+            spawn_line_forwarder(sub.name.clone(), header.clone(), StreamKind::Stderr, err)
+        });
+
+        let status = child
+            .wait()
+            .map_err(|err| format!("[{}] failed to run `{}`: {}", sub.name, step.join(" "), err))?;
+        if let Some(handle) = stdout_forwarder {
+            let _ = handle.join();
+        }
+        if let Some(handle) = stderr_forwarder {
+            let _ = handle.join();
+        }
+
+        if !status.success() {
+            return Err(format!(
+                "[{}] `{}` exited with {}",
+                sub.name,
+                step.join(" "),
+                status
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Builds every given subserver concurrently, gated by a token pool sized to
+/// `jobs` so builds don't oversubscribe the machine. Collects failures from
+/// all builders and reports them together rather than aborting on the first.
+fn build_all_parallel(config: &Config, subs: Vec<&SubserverConfig>, jobs: usize) {
+    let jobs = jobs.max(1);
+    let (tx, rx) = mpsc::sync_channel::<()>(jobs);
+    for _ in 0..jobs {
+        tx.send(()).unwrap();
+    }
+    let rx = Mutex::new(rx);
+    let failures = Mutex::new(Vec::new());
+
+    thread::scope(|scope| {
+        for &sub in &subs {
+            let tx = tx.clone();
+            let rx = &rx;
+            let failures = &failures;
+            scope.spawn(move || {
+                rx.lock().unwrap().recv().unwrap();
+                if let Err(err) = node_build(config, sub) {
+                    failures.lock().unwrap().push(err);
+                }
+                tx.send(()).unwrap();
+            });
+        }
+    });
+
+    let failures = failures.into_inner().unwrap();
+    if !failures.is_empty() {
+        for failure in &failures {
+            Style::StatusInfo.println(failure.clone());
+        }
+        panic!("{} subserver build(s) failed", failures.len());
+    }
+}
+
+/// The master side of a PTY allocated for a child, kept alive for the
+/// lifetime of the child so its output can be read and its window size
+/// kept in sync with the parent terminal.
+struct ChildPty {
+    master: OwnedFd,
+}
+impl Drop for ChildPty {
+    /// Prunes this PTY from `PTY_MASTERS` so a respawn (crash backoff,
+    /// `--watch` rebuild, console `restart`/`rebuild`) doesn't leave a stale
+    /// fd number behind for SIGWINCH to `ioctl` once the OS reuses it.
+    fn drop(&mut self) {
+        let fd = self.master.as_raw_fd();
+        PTY_MASTERS.lock().unwrap().retain(|&master| master != fd);
+    }
+}
+
+fn header_for(
+    config: &Config,
+    sub: &SubserverConfig,
+) -> [crossterm::style::StyledContent<String>; 2] {
+    let n_spaces = config
+        .subservers
+        .iter()
+        .map(|s| s.name.len())
+        .max()
+        .unwrap_or(0)
+        - sub.name.len();
+    let color = sub.color.as_crossterm();
+    [
+        format!(" {}{} ", sub.name, " ".repeat(n_spaces)).with(color),
+        "  ".to_string().on(color),
+    ]
+}
+
+/// Which of a child's output streams a forwarder thread is relaying. In PTY
+/// mode, `Stdout` actually carries the PTY's merged stdin/stdout stream;
+/// stderr is still piped separately so it can be marked on its own, unless
+/// `inherit_stdouts`/`-m` is given, in which case it goes to the PTY too.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum StreamKind {
+    Stdout,
+    Stderr,
+}
+impl StreamKind {
+    fn label(self) -> &'static str {
+        match self {
+            Self::Stdout => "stdout",
+            Self::Stderr => "stderr",
+        }
+    }
+    /// Hazard-stripe marker used in place of the normal per-server color
+    /// block, so error output stands out from a server's regular logs.
+    fn error_marker() -> [crossterm::style::StyledContent<String>; 2] {
+        [" ".to_string().on_red(), " ".to_string().on_dark_yellow()]
+    }
+}
+
+/// All forwarder threads print through this lock so a stdout line and a
+/// stderr line from the same child can never interleave mid-write.
+static PRINT_LOCK: std::sync::Mutex<()> = std::sync::Mutex::new(());
+
+fn spawn_line_forwarder(
+    name: String,
+    header: [crossterm::style::StyledContent<String>; 2],
+    kind: StreamKind,
+    mut reader: impl Read + Send + 'static,
+) -> thread::JoinHandle<()> {
+    thread::spawn(move || {
+        {
+            let _guard = PRINT_LOCK.lock().unwrap();
             execute!(
                 stdout(),
                 PrintStyledContent(
-                    format!(" [{}] :: start of stdout ", name)
+                    format!(" [{}] :: start of {} ", name, kind.label())
                         .white()
                         .on_dark_yellow()
                 ),
                 Print("\r\n"),
             )
             .unwrap();
-            let mut line = Vec::new();
-            for byte in child_stdout.bytes() {
-                match byte {
-                    Err(err) => {
-                        dbg!(err);
-                        break;
-                    }
-                    Ok(b) => {
+        }
+        let mut line = Vec::new();
+        let mut byte = [0u8; 1];
+        loop {
+            match reader.read(&mut byte) {
+                Ok(0) => break,
+                Err(err) => {
+                    dbg!(err);
+                    break;
+                }
+                Ok(_) => {
+                    line.push(byte[0]);
+                    if byte[0] == b'\n' {
+                        let _guard = PRINT_LOCK.lock().unwrap();
                         let mut stdout = stdout();
-                        line.push(b);
-                        if b == '\n' as u8 {
+                        if kind == StreamKind::Stderr {
+                            let marker = StreamKind::error_marker();
+                            queue!(
+                                stdout,
+                                PrintStyledContent(header[0].clone()),
+                                PrintStyledContent(marker[0].clone()),
+                                PrintStyledContent(marker[1].clone()),
+                                Print(" ")
+                            )
+                            .unwrap();
+                        } else {
                             queue!(
                                 stdout,
                                 PrintStyledContent(header[0].clone()),
@@ -236,38 +855,203 @@ fn node_run(name: &'static str, args: ParsedArgs) -> Child {
                                 Print(" ")
                             )
                             .unwrap();
-                            stdout.write_all(&line).unwrap();
-                            line.clear();
-                            stdout.flush().unwrap();
                         }
+                        stdout.write_all(&line).unwrap();
+                        line.clear();
+                        stdout.flush().unwrap();
                     }
                 }
             }
-            execute!(
-                stdout(),
-                PrintStyledContent(
-                    format!("\n [{}] :: end of stdout ", name)
-                        .white()
-                        .on_dark_yellow()
-                ),
-                Print("\r\n"),
-            )
-            .unwrap();
+        }
+        let _guard = PRINT_LOCK.lock().unwrap();
+        execute!(
+            stdout(),
+            PrintStyledContent(
+                format!("\n [{}] :: end of {} ", name, kind.label())
+                    .white()
+                    .on_dark_yellow()
+            ),
+            Print("\r\n"),
+        )
+        .unwrap();
+    })
+}
+
+/// A handle for writing to a child's stdin, used by the interactive console
+/// to route typed input to a chosen subserver.
+enum ChildInput {
+    Pty(fs::File),
+    Piped(std::process::ChildStdin),
+    None,
+}
+impl ChildInput {
+    fn send_line(&mut self, line: &str) -> std::io::Result<()> {
+        let writer: &mut dyn Write = match self {
+            Self::Pty(writer) => writer,
+            Self::Piped(writer) => writer,
+            Self::None => return Ok(()),
+        };
+        writer.write_all(line.as_bytes())?;
+        writer.write_all(b"\n")
+    }
+}
+
+fn node_run(
+    config: &Config,
+    sub: &SubserverConfig,
+    args: &ParsedArgs,
+) -> (Child, Option<ChildPty>, ChildInput) {
+    let [program, start_args @ ..] = sub.start_command.as_slice() else {
+        panic!("[{}] has an empty start_command", sub.name);
+    };
+    let mut command = Command::new(program);
+    command.args(start_args);
+    if let (Some(port), Some(template)) = (
+        args.port_override_for(&sub.name),
+        sub.port_arg_template.as_deref(),
+    ) {
+        command
+            .arg("--")
+            .arg(template.replace("{port}", &port.to_string()));
+    }
+    command.current_dir(get_subserver_cwd(&sub.name));
+
+    let header = header_for(config, sub);
+
+    if args.use_pty {
+        let (cols, rows) = terminal::size().unwrap_or((80, 24));
+        let (master, slave) = open_pty(cols, rows);
+
+        command
+            .stdin(pty_stdio(&slave))
+            .stdout(pty_stdio(&slave))
+            .stderr(if args.distinguish_child_stdouts {
+                // Piped separately so it can still be marked and bannered on
+                // its own; the PTY slave would otherwise merge it into stdout
+                // before the forwarder ever saw it.
+                Stdio::piped()
+            } else {
+                pty_stdio(&slave)
+            });
+        let mut child = command.spawn().unwrap();
+        drop(slave);
+
+        let pty_reader = fs::File::from(master.try_clone().unwrap());
+        spawn_line_forwarder(
+            sub.name.clone(),
+            header.clone(),
+            StreamKind::Stdout,
+            pty_reader,
+        );
+        if let Some(child_stderr) = child.stderr.take() {
+            spawn_line_forwarder(sub.name.clone(), header, StreamKind::Stderr, child_stderr);
+        }
+
+        let pty_writer = fs::File::from(master.try_clone().unwrap());
+        PTY_MASTERS.lock().unwrap().push(master.as_raw_fd());
+        sleep(Duration::from_millis(10));
+        return (
+            child,
+            Some(ChildPty { master }),
+            ChildInput::Pty(pty_writer),
+        );
+    }
+
+    command
+        .stdin(if args.distinguish_child_stdouts {
+            Stdio::piped()
+        } else {
+            Stdio::inherit()
+        })
+        .stdout(if args.distinguish_child_stdouts {
+            Stdio::piped()
+        } else {
+            Stdio::inherit()
+        })
+        .stderr(if args.distinguish_child_stdouts {
+            Stdio::piped()
+        } else {
+            Stdio::inherit()
         });
+    let mut child = command.spawn().unwrap();
+
+    let input = child
+        .stdin
+        .take()
+        .map_or(ChildInput::None, ChildInput::Piped);
+
+    if let Some(child_stdout) = child.stdout.take() {
+        spawn_line_forwarder(
+            sub.name.clone(),
+            header.clone(),
+            StreamKind::Stdout,
+            child_stdout,
+        );
+    }
+    if let Some(child_stderr) = child.stderr.take() {
+        spawn_line_forwarder(sub.name.clone(), header, StreamKind::Stderr, child_stderr);
     }
 
     sleep(Duration::from_millis(10));
 
-    child
+    (child, None, input)
 }
 
-fn git_clone() {
+/// Opens a pseudo-terminal pair sized to `(cols, rows)`, returning the
+/// master end (read by the parent) and the slave end (handed to the child
+/// as its stdio). This only sets the initial size; subsequent resizes of
+/// the parent terminal are propagated to every live master fd on SIGWINCH
+/// (see `install_sigwinch_handler`).
+fn open_pty(cols: u16, rows: u16) -> (OwnedFd, OwnedFd) {
+    let winsize = libc::winsize {
+        ws_row: rows,
+        ws_col: cols,
+        ws_xpixel: 0,
+        ws_ypixel: 0,
+    };
+    let mut master: RawFd = 0;
+    let mut slave: RawFd = 0;
+    let ret = unsafe {
+        libc::openpty(
+            &mut master,
+            &mut slave,
+            std::ptr::null_mut(),
+            std::ptr::null_mut(),
+            &winsize as *const _ as *mut _,
+        )
+    };
+    if ret != 0 {
+        panic!("failed to open pty: {}", std::io::Error::last_os_error());
+    }
+    unsafe { (OwnedFd::from_raw_fd(master), OwnedFd::from_raw_fd(slave)) }
+}
+
+fn pty_stdio(slave: &OwnedFd) -> Stdio {
+    Stdio::from(slave.try_clone().unwrap())
+}
+
+fn pty_set_winsize(fd: RawFd, cols: u16, rows: u16) {
+    let winsize = libc::winsize {
+        ws_row: rows,
+        ws_col: cols,
+        ws_xpixel: 0,
+        ws_ypixel: 0,
+    };
+    unsafe {
+        libc::ioctl(fd, libc::TIOCSWINSZ, &winsize);
+    }
+}
+
+fn git_clone(config: &Config) {
     let base_url = get_base_url();
-    for name in SUBSERVER_NAMES {
-        Style::StatusInfo.println(format!("[{}] download", name));
-        let mut url = base_url.clone();
-        url += name;
-        url += ".git";
+    for sub in &config.subservers {
+        Style::StatusInfo.println(format!("[{}] download", sub.name));
+        let url = sub.repo_url.clone().unwrap_or_else(|| {
+            let mut url = base_url.clone();
+            url += &sub.name;
+            url += ".git";
+            url
+        });
         if !Command::new("git")
             .arg("clone")
             .arg(url)
@@ -280,14 +1064,15 @@ fn git_clone() {
         }
     }
 }
-fn git_pull() -> Vec<&'static str> {
-    SUBSERVER_NAMES
-        .into_iter()
-        .filter_map(|name| {
-            Style::StatusInfo.println(format!("[{}] download updates", name));
+fn git_pull(config: &Config) -> Vec<&SubserverConfig> {
+    config
+        .subservers
+        .iter()
+        .filter_map(|sub| {
+            Style::StatusInfo.println(format!("[{}] download updates", sub.name));
             let output = Command::new("git")
                 .arg("pull")
-                .current_dir(get_subserver_cwd(name).to_str().unwrap())
+                .current_dir(get_subserver_cwd(&sub.name).to_str().unwrap())
                 .output()
                 .unwrap();
             if !output.status.success() {
@@ -298,7 +1083,7 @@ fn git_pull() -> Vec<&'static str> {
             if String::from_utf8(output.stdout).unwrap().trim() == "Already up to date." {
                 None
             } else {
-                Some(name)
+                Some(sub)
             }
         })
         .collect()
@@ -348,3 +1133,199 @@ impl Style {
         };
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use config::SubserverColor;
+
+    fn parse(args: &[&str]) -> ParsedArgs {
+        std::iter::once("floormedia".to_string())
+            .chain(args.iter().map(|s| s.to_string()))
+            .into()
+    }
+
+    #[test]
+    fn defaults_with_no_args() {
+        let args = parse(&[]);
+        assert!(args.distinguish_child_stdouts);
+        assert!(args.port_overrides.is_empty());
+        assert!(!args.watch);
+    }
+
+    #[test]
+    fn parses_port_override() {
+        let args = parse(&["-p", "backend=9000"]);
+        assert_eq!(args.port_override_for("backend"), Some(9000));
+        assert_eq!(args.port_override_for("frontend"), None);
+    }
+
+    #[test]
+    fn ignores_malformed_port_override() {
+        let args = parse(&["-p", "not-a-port-spec"]);
+        assert!(args.port_overrides.is_empty());
+    }
+
+    #[test]
+    fn parses_watch_and_jobs_flags() {
+        let args = parse(&["-w", "-j", "3"]);
+        assert!(args.watch);
+        assert_eq!(args.jobs, 3);
+    }
+
+    #[test]
+    fn parses_no_pty_and_inherit_stdouts() {
+        let args = parse(&["-np", "-m"]);
+        assert!(!args.use_pty);
+        assert!(!args.distinguish_child_stdouts);
+    }
+
+    #[test]
+    fn ignores_unknown_flag() {
+        let args = parse(&["--not-a-real-flag"]);
+        assert!(!args.watch);
+    }
+
+    fn dummy_managed_child() -> ManagedChild {
+        let child = Command::new("cat")
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .spawn()
+            .expect("failed to spawn dummy child for test");
+        ManagedChild {
+            child,
+            _pty: None,
+            input: ChildInput::None,
+            restart_attempts: 0,
+            next_restart_at: None,
+        }
+    }
+
+    fn sub_config(name: &str) -> SubserverConfig {
+        SubserverConfig {
+            name: name.to_string(),
+            repo_url: None,
+            build_steps: Vec::new(),
+            start_command: vec!["true".to_string()],
+            port_arg_template: None,
+            color: SubserverColor::Blue,
+        }
+    }
+
+    #[test]
+    fn at_prefix_focuses_known_server() {
+        let config = Config {
+            subservers: vec![sub_config("backend")],
+        };
+        let args = parse(&[]);
+        let mut children = HashMap::new();
+        children.insert("backend".to_string(), dummy_managed_child());
+        let mut focused = None;
+        let mut pending = HashSet::new();
+        let (tx, _rx) = mpsc::channel();
+
+        handle_console_line(
+            &config,
+            &args,
+            &mut children,
+            &mut focused,
+            &mut pending,
+            &tx,
+            "@backend",
+        );
+
+        assert_eq!(focused.as_deref(), Some("backend"));
+        children.get_mut("backend").unwrap().child.kill().ok();
+    }
+
+    #[test]
+    fn at_prefix_rejects_unknown_server() {
+        let config = Config { subservers: vec![] };
+        let args = parse(&[]);
+        let mut children = HashMap::new();
+        let mut focused = None;
+        let mut pending = HashSet::new();
+        let (tx, _rx) = mpsc::channel();
+
+        handle_console_line(
+            &config,
+            &args,
+            &mut children,
+            &mut focused,
+            &mut pending,
+            &tx,
+            "@unknown",
+        );
+
+        assert_eq!(focused, None);
+    }
+
+    #[test]
+    fn bare_line_with_no_focus_is_a_no_op() {
+        let config = Config { subservers: vec![] };
+        let args = parse(&[]);
+        let mut children = HashMap::new();
+        let mut focused = None;
+        let mut pending = HashSet::new();
+        let (tx, _rx) = mpsc::channel();
+
+        handle_console_line(
+            &config,
+            &args,
+            &mut children,
+            &mut focused,
+            &mut pending,
+            &tx,
+            "hello",
+        );
+
+        assert!(children.is_empty());
+        assert!(pending.is_empty());
+    }
+
+    #[test]
+    fn restart_unknown_server_does_not_mark_pending() {
+        let config = Config { subservers: vec![] };
+        let args = parse(&[]);
+        let mut children = HashMap::new();
+        let mut pending = HashSet::new();
+        let (tx, _rx) = mpsc::channel();
+
+        console_restart(&config, &args, &mut children, &mut pending, &tx, "backend");
+
+        assert!(pending.is_empty());
+    }
+
+    #[test]
+    fn restart_known_server_marks_pending_and_removes_it() {
+        let config = Config {
+            subservers: vec![sub_config("backend")],
+        };
+        let args = parse(&[]);
+        let mut children = HashMap::new();
+        children.insert("backend".to_string(), dummy_managed_child());
+        let mut pending = HashSet::new();
+        let (tx, _rx) = mpsc::channel();
+
+        console_restart(&config, &args, &mut children, &mut pending, &tx, "backend");
+
+        assert!(pending.contains("backend"));
+        assert!(!children.contains_key("backend"));
+    }
+
+    #[test]
+    fn rebuild_known_server_marks_pending_without_removing_it() {
+        let config = Config {
+            subservers: vec![sub_config("backend")],
+        };
+        let mut children = HashMap::new();
+        children.insert("backend".to_string(), dummy_managed_child());
+        let mut pending = HashSet::new();
+        let (tx, _rx) = mpsc::channel();
+
+        console_rebuild(&config, &children, &mut pending, &tx, "backend");
+
+        assert!(pending.contains("backend"));
+        children.get_mut("backend").unwrap().child.kill().ok();
+    }
+}